@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use super::events::{self, MalformedReason};
+use super::{parse_ssh_config, Expression, File, Position, Span};
+
+/// The specific reason a [`ParseDiagnostic`] was raised.
+///
+/// These mirror cases the line parser already detects internally but collapses into an opaque
+/// [`Expression::Malformed`].
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum DiagnosticKind {
+    /// A `"`-quoted argument has no matching closing quote before the end of the line.
+    UnterminatedQuotedArgument,
+    /// An unquoted argument contains a `#`, which would otherwise be ambiguous with a trailing
+    /// comment.
+    UnescapedHash,
+    /// The keyword contains non-alphabetic characters (as judged by [`char::is_alphabetic`]).
+    NonAlphabeticKeyword,
+    /// A keyword is present but is not followed by a separator and arguments.
+    MissingSeparator,
+}
+
+/// A single diagnostic raised while parsing a line that could not be turned into a well-formed
+/// [`ConfigurationOptions`](super::Expression::ConfigurationOptions) expression.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct ParseDiagnostic {
+    pub span: Span,
+    pub kind: DiagnosticKind,
+}
+
+/// Parses `content` the same way [`parse_ssh_config`](super::parse_ssh_config) does, but also
+/// returns precise, typed diagnostics for every line that could not be parsed as a well-formed
+/// expression, instead of only the opaque [`Expression::Malformed`](super::Expression::Malformed)
+/// it collapses to in the returned [`File`].
+///
+/// Diagnostics are derived from the very same [`Malformed`](Expression::Malformed) text and
+/// [`Span`] the returned [`File`] already computed, re-running only [`events::lex_core`] (the one
+/// lexer [`File`], [`super::parse_events`] and this function all share) over that text to recover
+/// *why* it was malformed.
+pub fn parse_ssh_config_diagnostics(content: &str) -> (File, Vec<ParseDiagnostic>) {
+    let file = parse_ssh_config(content, None);
+
+    let mut diagnostics = Vec::new();
+    for line in &file.lines {
+        if let Expression::Malformed(span, text) = &line.expression {
+            let mut discarded = VecDeque::new();
+            if let Some(reason) = events::lex_core(text, &mut discarded) {
+                diagnostics.push(diagnostic_for(span.start, text, reason));
+            }
+        }
+    }
+
+    (file, diagnostics)
+}
+
+/// Builds the [`ParseDiagnostic`] for a [`MalformedReason`] found while re-lexing a malformed
+/// expression's `text`, given that `text` starts at `base`.
+fn diagnostic_for(base: Position, text: &str, reason: MalformedReason) -> ParseDiagnostic {
+    let (range, kind) = match reason {
+        MalformedReason::NonAlphabeticKeyword(range) => (range, DiagnosticKind::NonAlphabeticKeyword),
+        MalformedReason::MissingSeparator(range) => (range, DiagnosticKind::MissingSeparator),
+        MalformedReason::UnterminatedQuotedArgument(range) => {
+            (range, DiagnosticKind::UnterminatedQuotedArgument)
+        }
+        MalformedReason::UnescapedHash(range) => (range, DiagnosticKind::UnescapedHash),
+    };
+    ParseDiagnostic {
+        span: Span {
+            start: position_within(text, base, range.start),
+            end: position_within(text, base, range.end),
+        },
+        kind,
+    }
+}
+
+/// Computes the [`Position`] of the byte offset `byte_in_text` within `text`, given that `text`
+/// itself starts at `base`.
+fn position_within(text: &str, base: Position, byte_in_text: usize) -> Position {
+    Position {
+        offset: base.offset + byte_in_text,
+        line: base.line,
+        column: base.column + text[..byte_in_text].chars().count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_diagnostics_for_well_formed_config_test() {
+        let (_, diagnostics) = parse_ssh_config_diagnostics("Host example.com\n\tUser root\n");
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn reports_unterminated_quoted_argument_test() {
+        let (_, diagnostics) = parse_ssh_config_diagnostics("Host \"lol\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].kind,
+            DiagnosticKind::UnterminatedQuotedArgument
+        );
+        assert_eq!(diagnostics[0].span.start.line, 1);
+        assert_eq!(diagnostics[0].span.start.column, 6);
+    }
+
+    #[test]
+    fn reports_unescaped_hash_test() {
+        let (_, diagnostics) = parse_ssh_config_diagnostics("Host k#k\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::UnescapedHash);
+        assert_eq!(diagnostics[0].span.start.column, 7);
+    }
+
+    #[test]
+    fn reports_non_alphabetic_keyword_test() {
+        let (_, diagnostics) = parse_ssh_config_diagnostics("123\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::NonAlphabeticKeyword);
+        assert_eq!(diagnostics[0].span.start.column, 1);
+    }
+
+    #[test]
+    fn reports_missing_separator_test() {
+        let (_, diagnostics) = parse_ssh_config_diagnostics("Host\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::MissingSeparator);
+    }
+
+    #[test]
+    fn reports_missing_separator_for_keyword_with_no_arguments_test() {
+        let (_, diagnostics) = parse_ssh_config_diagnostics("Host =\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::MissingSeparator);
+    }
+
+    #[test]
+    fn reports_non_alphabetic_keyword_for_digit_glued_to_keyword_test() {
+        let (_, diagnostics) = parse_ssh_config_diagnostics("Host0 example.com\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::NonAlphabeticKeyword);
+        assert_eq!(diagnostics[0].span.start.column, 1);
+        assert_eq!(diagnostics[0].span.end.column, 6);
+    }
+
+    #[test]
+    fn no_diagnostics_for_bare_equals_separator_test() {
+        let (_, diagnostics) = parse_ssh_config_diagnostics("Host=example.com\n");
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn tracks_line_numbers_across_multiple_lines_test() {
+        let (_, diagnostics) = parse_ssh_config_diagnostics("Host example.com\nHost k#k\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span.start.line, 2);
+    }
+}
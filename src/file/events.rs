@@ -0,0 +1,390 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Borrowed counterpart of [`ArgumentToken`](super::ArgumentToken), used by the [`Event`] stream
+/// so that scanning a config for a single keyword never has to allocate owned strings for
+/// arguments the caller doesn't care about.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ArgTokenRef<'a> {
+    /// See [`ArgumentToken::Pure`](super::ArgumentToken::Pure).
+    Pure(&'a str),
+    /// See [`ArgumentToken::Quoted`](super::ArgumentToken::Quoted). The slice does not include
+    /// the surrounding double quotes.
+    Quoted(&'a str),
+    /// See [`ArgumentToken::Whitespace`](super::ArgumentToken::Whitespace).
+    Whitespace(&'a str),
+}
+
+/// A single fine-grained piece of ssh_config syntax, borrowed from the input it was lexed from.
+///
+/// Unlike [`Line`](super::Line)/[`Expression`](super::Expression), events carry no allocation of
+/// their own: a full line is represented as several consecutive events (leading whitespace,
+/// keyword, separator, one event per argument token, trailing whitespace, newline) rather than as
+/// one owned structure, which makes [`Events`] suitable for scanning large or concatenated
+/// configs without materializing them.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Event<'a> {
+    /// A comment line's text, starting with `#`.
+    Comment(&'a str),
+    /// A `ConfigurationOptions` keyword, e.g. `Host`.
+    Keyword(&'a str),
+    /// The separator between a keyword and its arguments (whitespace and/or `=`).
+    Separator(&'a str),
+    /// A single argument token.
+    Argument(ArgTokenRef<'a>),
+    /// A run of leading or trailing whitespace on a line.
+    Whitespace(&'a str),
+    /// The line terminator (`\n` or `\r\n`), if any.
+    Newline(&'a str),
+    /// A line that is not empty, a comment, or a well-formed `ConfigurationOptions` expression.
+    Malformed(&'a str),
+}
+
+/// Produces a borrowing, zero-copy [`Events`] iterator over the fine-grained syntax of an
+/// ssh_config file.
+///
+/// [`Line`](super::Line) construction in `file.rs` calls the very same [`split_indent`] and
+/// [`lex_core`] helpers this iterator uses, so the two never disagree on what is well-formed —
+/// this is a thinner, allocation-free view of the same lexer, not an independent one.
+pub fn parse_events(content: &str) -> Events<'_> {
+    Events {
+        remaining: content,
+        pending: VecDeque::new(),
+    }
+}
+
+/// Iterator over the [`Event`]s of an ssh_config file, produced by [`parse_events`].
+pub struct Events<'a> {
+    remaining: &'a str,
+    pending: VecDeque<Event<'a>>,
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Event<'a>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if self.remaining.is_empty() {
+                return None;
+            }
+            self.lex_next_line();
+        }
+    }
+}
+
+impl<'a> Events<'a> {
+    /// Lexes the next physical line off `self.remaining` (including its terminator, if any) and
+    /// queues its events in `self.pending`.
+    fn lex_next_line(&mut self) {
+        let (line, terminator, rest) = split_first_line(self.remaining);
+        self.remaining = rest;
+
+        let (indent_prefix, core, indent_suffix) = split_indent(line);
+
+        if !indent_prefix.is_empty() {
+            self.pending.push_back(Event::Whitespace(indent_prefix));
+        }
+        // The reason a malformed core failed to lex is only useful to diagnostics.rs, which calls
+        // `lex_core` directly; a plain event stream has no slot to carry it.
+        let _ = lex_core(core, &mut self.pending);
+        if !indent_suffix.is_empty() {
+            self.pending.push_back(Event::Whitespace(indent_suffix));
+        }
+        if !terminator.is_empty() {
+            self.pending.push_back(Event::Newline(terminator));
+        }
+    }
+}
+
+/// Splits `content` into its first physical line (without terminator), that line's terminator
+/// (`"\n"`, `"\r\n"`, or `""` if `content` has no more newlines), and the remaining content.
+pub(crate) fn split_first_line(content: &str) -> (&str, &str, &str) {
+    match content.find('\n') {
+        Some(index) => {
+            let (line, rest) = content.split_at(index);
+            let rest = &rest[1..];
+            if let Some(line) = line.strip_suffix('\r') {
+                (line, &content[line.len()..line.len() + 2], rest)
+            } else {
+                (line, &content[line.len()..line.len() + 1], rest)
+            }
+        }
+        None => (content, "", ""),
+    }
+}
+
+/// Splits a physical line (terminator already stripped) into its leading whitespace, core (the
+/// part that is actually lexed into [`Event`]s), and trailing whitespace.
+pub(crate) fn split_indent(line: &str) -> (&str, &str, &str) {
+    let indent_prefix_len = line
+        .char_indices()
+        .take_while(|(_, c)| c.is_whitespace())
+        .last()
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let indent_suffix_len = line
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_whitespace())
+        .count();
+    let indent_suffix_start = line.len() - indent_suffix_len.min(line.len() - indent_prefix_len);
+
+    (
+        &line[..indent_prefix_len],
+        &line[indent_prefix_len..indent_suffix_start],
+        &line[indent_suffix_start..],
+    )
+}
+
+/// The reason [`lex_core`]/[`lex_arguments`] judged a line malformed, paired with the byte range
+/// (relative to the `core` text passed to [`lex_core`]) that triggered it.
+///
+/// This is the one place that distinguishes *why* a line is [`Event::Malformed`]/
+/// [`Expression::Malformed`](super::Expression::Malformed); `diagnostics.rs` calls [`lex_core`]
+/// directly to recover it instead of re-deriving it with a second parser of its own.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub(crate) enum MalformedReason {
+    /// The keyword contains non-alphabetic characters (as judged by [`char::is_alphabetic`]).
+    NonAlphabeticKeyword(Range<usize>),
+    /// A keyword is present but is not followed by a separator and arguments.
+    MissingSeparator(Range<usize>),
+    /// A `"`-quoted argument has no matching closing quote before the end of the line.
+    UnterminatedQuotedArgument(Range<usize>),
+    /// An unquoted argument contains a `#`, which would otherwise be ambiguous with a trailing
+    /// comment.
+    UnescapedHash(Range<usize>),
+}
+
+impl MalformedReason {
+    /// Rebases the carried byte range by `by`, turning a range relative to the arguments
+    /// substring `lex_arguments` was given into one relative to the whole `core` `lex_core` was
+    /// given.
+    fn shift(self, by: usize) -> Self {
+        fn shift_range(range: Range<usize>, by: usize) -> Range<usize> {
+            (range.start + by)..(range.end + by)
+        }
+        match self {
+            Self::NonAlphabeticKeyword(range) => Self::NonAlphabeticKeyword(shift_range(range, by)),
+            Self::MissingSeparator(range) => Self::MissingSeparator(shift_range(range, by)),
+            Self::UnterminatedQuotedArgument(range) => {
+                Self::UnterminatedQuotedArgument(shift_range(range, by))
+            }
+            Self::UnescapedHash(range) => Self::UnescapedHash(shift_range(range, by)),
+        }
+    }
+}
+
+/// Lexes the trimmed core of a line (i.e. with indentation already stripped) into events,
+/// pushing them onto `out`. Returns `Some` with the reason when `core` is malformed (in which
+/// case a single [`Event::Malformed`] was pushed instead of the usual `Keyword`/`Separator`/
+/// `Argument` events).
+pub(crate) fn lex_core<'a>(
+    core: &'a str,
+    out: &mut VecDeque<Event<'a>>,
+) -> Option<MalformedReason> {
+    if core.is_empty() {
+        return None;
+    }
+    if core.starts_with('#') {
+        out.push_back(Event::Comment(core));
+        return None;
+    }
+
+    let keyword_len = core
+        .char_indices()
+        .take_while(|(_, c)| c.is_alphabetic())
+        .last()
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let word_len = || {
+        core.char_indices()
+            .take_while(|(_, c)| !c.is_whitespace())
+            .last()
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0)
+    };
+    if keyword_len == 0 {
+        out.push_back(Event::Malformed(core));
+        return Some(MalformedReason::NonAlphabeticKeyword(0..word_len()));
+    }
+
+    let separator_len = core[keyword_len..]
+        .char_indices()
+        .take_while(|(_, c)| c.is_whitespace() || *c == '=')
+        .last()
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let separator = &core[keyword_len..keyword_len + separator_len];
+
+    if separator.is_empty() {
+        out.push_back(Event::Malformed(core));
+        let word_len = word_len();
+        return Some(if word_len > keyword_len {
+            // The keyword's alphabetic prefix doesn't cover the whole whitespace-delimited word
+            // (e.g. `Host0` or `Ho#st`); the extra characters are glued directly onto it with no
+            // separator.
+            MalformedReason::NonAlphabeticKeyword(0..word_len)
+        } else {
+            // The keyword consumes the entire word (e.g. bare `Host`) and nothing follows it.
+            MalformedReason::MissingSeparator(0..keyword_len)
+        });
+    }
+    if !(separator.trim().is_empty() || separator.trim() == "=") {
+        out.push_back(Event::Malformed(core));
+        return Some(MalformedReason::MissingSeparator(0..keyword_len));
+    }
+
+    let arguments = &core[keyword_len + separator_len..];
+    if arguments.is_empty() {
+        out.push_back(Event::Malformed(core));
+        return Some(MalformedReason::MissingSeparator(
+            0..(keyword_len + separator_len),
+        ));
+    }
+
+    match lex_arguments(arguments) {
+        Ok(argument_events) => {
+            out.push_back(Event::Keyword(&core[..keyword_len]));
+            out.push_back(Event::Separator(separator));
+            out.extend(argument_events);
+            None
+        }
+        Err(reason) => {
+            out.push_back(Event::Malformed(core));
+            Some(reason.shift(keyword_len + separator_len))
+        }
+    }
+}
+
+/// Lexes an arguments expression into a sequence of [`Event::Argument`]s, returning the
+/// [`MalformedReason`] (with its byte range relative to `content`) if it is malformed (an
+/// unterminated quote or an unescaped `#` inside an argument — `content` is never empty here,
+/// since [`lex_core`] handles that case itself).
+fn lex_arguments(content: &str) -> Result<Vec<Event<'_>>, MalformedReason> {
+    let mut events = Vec::new();
+    let mut remaining = content;
+    let mut offset = 0usize;
+
+    while !remaining.is_empty() {
+        if remaining.starts_with(|c: char| c.is_whitespace()) {
+            let len = remaining
+                .char_indices()
+                .take_while(|(_, c)| c.is_whitespace())
+                .last()
+                .map(|(i, c)| i + c.len_utf8())
+                .unwrap_or(0);
+            let (whitespace, rest) = remaining.split_at(len);
+            remaining = rest;
+            offset += len;
+            events.push(Event::Argument(ArgTokenRef::Whitespace(whitespace)));
+        } else if let Some(rest) = remaining.strip_prefix('"') {
+            let mut prev = '\0';
+            let mut token_len = 0;
+            let mut found_end_quote = false;
+            for c in rest.chars() {
+                if prev != '\\' && c == '"' {
+                    found_end_quote = true;
+                    break;
+                }
+                token_len += c.len_utf8();
+                prev = c;
+            }
+
+            if !found_end_quote {
+                return Err(MalformedReason::UnterminatedQuotedArgument(
+                    offset..(offset + 1 + token_len),
+                ));
+            }
+
+            let quoted = &rest[..token_len];
+            remaining = &rest[token_len + 1..];
+            offset += 1 + token_len + 1;
+            events.push(Event::Argument(ArgTokenRef::Quoted(quoted)));
+        } else {
+            let len = remaining
+                .char_indices()
+                .take_while(|(_, c)| !c.is_whitespace())
+                .last()
+                .map(|(i, c)| i + c.len_utf8())
+                .unwrap_or(0);
+            let (pure, rest) = remaining.split_at(len);
+
+            if let Some(hash_index) = pure.find('#') {
+                return Err(MalformedReason::UnescapedHash(
+                    (offset + hash_index)..(offset + hash_index + 1),
+                ));
+            }
+
+            remaining = rest;
+            offset += len;
+            events.push(Event::Argument(ArgTokenRef::Pure(pure)));
+        }
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_of_simple_config_test() {
+        let events: Vec<Event> =
+            parse_events("# a comment\nHost example.com\n\tUser root\n").collect();
+        assert_eq!(
+            events,
+            vec![
+                Event::Comment("# a comment"),
+                Event::Newline("\n"),
+                Event::Keyword("Host"),
+                Event::Separator(" "),
+                Event::Argument(ArgTokenRef::Pure("example.com")),
+                Event::Newline("\n"),
+                Event::Whitespace("\t"),
+                Event::Keyword("User"),
+                Event::Separator(" "),
+                Event::Argument(ArgTokenRef::Pure("root")),
+                Event::Newline("\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_preserve_whitespace_and_quoting_test() {
+        let events: Vec<Event> = parse_events("  Host \"a b\" \r\n").collect();
+        assert_eq!(
+            events,
+            vec![
+                Event::Whitespace("  "),
+                Event::Keyword("Host"),
+                Event::Separator(" "),
+                Event::Argument(ArgTokenRef::Quoted("a b")),
+                Event::Whitespace(" "),
+                Event::Newline("\r\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_of_malformed_line_test() {
+        let events: Vec<Event> = parse_events("Host\n").collect();
+        assert_eq!(events, vec![Event::Malformed("Host"), Event::Newline("\n")]);
+    }
+
+    #[test]
+    fn events_without_trailing_newline_test() {
+        let events: Vec<Event> = parse_events("User root").collect();
+        assert_eq!(
+            events,
+            vec![
+                Event::Keyword("User"),
+                Event::Separator(" "),
+                Event::Argument(ArgTokenRef::Pure("root")),
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
-use std::{fs, path::PathBuf};
+use std::{collections::VecDeque, fs, path::PathBuf};
 
-use super::{ArgumentToken, Expression, File, Line};
+use super::events::{self, ArgTokenRef, Event};
+use super::{ArgumentToken, Expression, File, Line, Position, Span};
 
 pub fn read_ssh_config(path: &PathBuf) -> std::io::Result<File> {
     fs::read_to_string(path).map(|content| parse_ssh_config(content.as_str(), Some(path.clone())))
@@ -8,9 +9,18 @@ pub fn read_ssh_config(path: &PathBuf) -> std::io::Result<File> {
 
 pub fn parse_ssh_config(content: &str, path: Option<PathBuf>) -> File {
     let mut lines = Vec::<Line>::new();
-    for line in content.lines() {
-        lines.push(parse_line(line));
+    let mut remaining = content;
+    let mut offset = 0usize;
+    let mut line_no = 1usize;
+
+    while !remaining.is_empty() {
+        let (line, terminator, rest) = events::split_first_line(remaining);
+        lines.push(parse_line_at(line, offset, line_no));
+        offset += line.len() + terminator.len();
+        line_no += 1;
+        remaining = rest;
     }
+
     File { lines, path }
 }
 
@@ -18,112 +28,104 @@ pub fn write_ssh_config(file: &File, path: PathBuf) -> std::io::Result<()> {
     fs::write(path, file.to_string())
 }
 
+#[cfg(test)]
 fn parse_line(line: &str) -> Line {
     let content = line.trim_end_matches(['\n', '\r']);
-    let mut it = content.chars();
-    if it.clone().any(|c| c == '\n') {
-        panic!("multiline string can not be parsed as a single line");
-    }
-    let indent_prefix: String = it.by_ref().take_while(|s| s.is_whitespace()).collect();
-    let indent_suffix_len = it.by_ref().rev().take_while(|s| s.is_whitespace()).count();
-    let indent_suffix: String = content[(content.len() - indent_suffix_len)..].to_string();
-
-    Line {
-        indent_prefix,
-        expression: parse_expression(line.trim()),
-        indent_suffix,
-    }
+    parse_line_at(content, 0, 1)
 }
 
-fn parse_expression(content: &str) -> Expression {
-    if content.is_empty() {
-        return Expression::Empty;
-    } else if content.starts_with('#') {
-        return Expression::Comment(content.to_string());
+/// Parses a single physical line (terminator already stripped) into a [`Line`], with every
+/// [`Span`] positioned relative to a source document where this line starts at byte `offset` and
+/// is line number `line_no`.
+fn parse_line_at(content: &str, offset: usize, line_no: usize) -> Line {
+    if content.contains('\n') {
+        // An embedded newline means the caller passed something other than a single line (e.g.
+        // the whole file); there's no sensible indent/expression split to compute, so surface it
+        // as malformed rather than panicking.
+        let span = span_of(content, offset, line_no, 1);
+        return Line {
+            span,
+            indent_prefix: String::new(),
+            expression: Expression::Malformed(span, content.to_string()),
+            indent_suffix: String::new(),
+        };
     }
 
-    let keyword: String = content.chars().take_while(|c| c.is_alphabetic()).collect();
+    let line_span = span_of(content, offset, line_no, 1);
 
-    if keyword.is_empty() {
-        return Expression::Malformed(content.to_string());
-    }
+    let (indent_prefix, core, indent_suffix) = events::split_indent(content);
+    let core_offset = offset + indent_prefix.len();
+    let core_column = indent_prefix.chars().count() + 1;
+    let core_span = span_of(core, core_offset, line_no, core_column);
 
-    let separator: String = content
-        .chars()
-        .skip(keyword.len())
-        .take_while(|c| c.is_whitespace() || c == &'=')
-        .collect();
+    let mut core_events = VecDeque::<Event>::new();
+    events::lex_core(core, &mut core_events);
 
-    if !separator.is_empty()
-        && (separator.as_str().trim().is_empty() || separator.as_str().trim() == "=")
-    {
-        if let Some(arguments_expression) =
-            parse_arguments_expression(content[(keyword.len() + separator.len())..].trim())
-        {
-            return Expression::ConfigurationOptions {
-                keyword,
-                separator,
-                arguments_expression,
-            };
-        } else {
-            return Expression::Malformed(content.to_string());
-        }
+    Line {
+        span: line_span,
+        indent_prefix: indent_prefix.to_string(),
+        expression: expression_from_events(core_span, core_events),
+        indent_suffix: indent_suffix.to_string(),
     }
-
-    Expression::Malformed(content.to_string())
 }
 
-fn parse_arguments_expression(content: &str) -> Option<Vec<ArgumentToken>> {
-    let mut arguments_expression = Vec::<ArgumentToken>::new();
-    let mut remaining = content;
-
-    while !remaining.is_empty() {
-        if remaining.starts_with(|c: char| c.is_whitespace()) {
-            let argument: String = remaining
-                .chars()
-                .take_while(|c| c.is_whitespace())
-                .collect();
-            remaining = &remaining[argument.len()..];
-            arguments_expression.push(ArgumentToken::Whitespace(argument));
-        } else if remaining.starts_with('"') {
-            let mut prev = '\0';
-            let mut token_size = 0;
-            let mut found_end_quote = false;
-            for c in remaining.chars().skip(1) {
-                if prev != '\\' && c == '"' {
-                    found_end_quote = true;
-                    break;
-                }
-                token_size += 1;
-                prev = c;
-            }
+/// Builds the [`Span`] of `text`, given that `text` starts at byte `offset`, on line `line_no`,
+/// column `column`.
+fn span_of(text: &str, offset: usize, line_no: usize, column: usize) -> Span {
+    Span {
+        start: Position {
+            offset,
+            line: line_no,
+            column,
+        },
+        end: Position {
+            offset: offset + text.len(),
+            line: line_no,
+            column: column + text.chars().count(),
+        },
+    }
+}
 
-            if found_end_quote {
-                let argument: String = remaining.chars().skip(1).take(token_size).collect();
-                remaining = &remaining[(1 + argument.len() + 1)..];
-                arguments_expression.push(ArgumentToken::Quoted(argument));
-            } else {
-                return None;
-            }
-        } else {
-            let argument: String = remaining
-                .chars()
-                .take_while(|c| !c.is_whitespace())
+/// Builds the owned [`Expression`] a [`Line`] stores from the [`Event`]s [`events::lex_core`]
+/// produced for its core text, so `Line` construction shares the exact same keyword/separator/
+/// argument lexing rules as [`parse_events`](super::parse_events) instead of a second,
+/// independently-written copy of them. `core_span` covers the whole core text, which is exactly
+/// what every [`Expression`] variant spans.
+fn expression_from_events(core_span: Span, mut events: VecDeque<Event>) -> Expression {
+    match events.pop_front() {
+        None => Expression::Empty(core_span),
+        Some(Event::Comment(comment)) => Expression::Comment(core_span, comment.to_string()),
+        Some(Event::Malformed(malformed)) => {
+            Expression::Malformed(core_span, malformed.to_string())
+        }
+        Some(Event::Keyword(keyword)) => {
+            let Some(Event::Separator(separator)) = events.pop_front() else {
+                unreachable!("lex_core always follows a Keyword with a Separator");
+            };
+            let arguments_expression = events
+                .into_iter()
+                .map(|event| match event {
+                    Event::Argument(token) => argument_token_from_event(token),
+                    _ => unreachable!("lex_core only follows a Separator with Argument events"),
+                })
                 .collect();
 
-            if argument.contains('#') {
-                return None;
+            Expression::ConfigurationOptions {
+                span: core_span,
+                keyword: keyword.to_string(),
+                separator: separator.to_string(),
+                arguments_expression,
             }
-
-            remaining = &remaining[argument.len()..];
-            arguments_expression.push(ArgumentToken::Pure(argument));
         }
+        Some(_) => unreachable!("lex_core's first event is unexpected"),
     }
+}
 
-    if arguments_expression.is_empty() {
-        None
-    } else {
-        Some(arguments_expression)
+fn argument_token_from_event(token: ArgTokenRef) -> ArgumentToken {
+    match token {
+        ArgTokenRef::Pure(value) => ArgumentToken::Pure(value.to_string()),
+        ArgTokenRef::Quoted(value) => ArgumentToken::Quoted(value.to_string()),
+        ArgTokenRef::Whitespace(value) => ArgumentToken::Whitespace(value.to_string()),
     }
 }
 
@@ -150,6 +152,23 @@ mod tests {
         "\t \t= \t\t",
     ];
 
+    /// Computes the [`Span`] of `text`, assuming `text` is ASCII (true of every fixture these
+    /// tests build), starts at byte/column `start`, and is entirely on line 1.
+    fn expected_span(start: usize, text: &str) -> Span {
+        Span {
+            start: Position {
+                offset: start,
+                line: 1,
+                column: start + 1,
+            },
+            end: Position {
+                offset: start + text.len(),
+                line: 1,
+                column: start + text.len() + 1,
+            },
+        }
+    }
+
     fn test_correct_line(
         keyword: &str,
         arguments_expression: &str,
@@ -163,11 +182,18 @@ mod tests {
                         indent_prefix, keyword, separator, arguments_expression, indent_suffix
                     );
                     let actual = parse_line(expected.as_str());
+                    let core = format!("{}{}{}", keyword, separator, arguments_expression);
+                    let line_content = format!(
+                        "{}{}{}",
+                        indent_prefix, core, indent_suffix
+                    );
                     assert_eq!(
                         actual,
                         Line {
+                            span: expected_span(0, &line_content),
                             indent_prefix: indent_prefix.to_string(),
                             expression: Expression::ConfigurationOptions {
+                                span: expected_span(indent_prefix.len(), &core),
                                 keyword: keyword.to_string(),
                                 separator: separator.to_string(),
                                 arguments_expression: arguments_expected.clone()
@@ -186,11 +212,16 @@ mod tests {
             for &indent_suffix in VALID_INDENTS {
                 let expected = format!("{}{}{}\n", indent_prefix, line, indent_suffix);
                 let actual = parse_line(expected.as_str());
+                let line_content = format!("{}{}{}", indent_prefix, line, indent_suffix);
                 assert_eq!(
                     actual,
                     Line {
+                        span: expected_span(0, &line_content),
                         indent_prefix: indent_prefix.to_string(),
-                        expression: Expression::Malformed(line.to_string()),
+                        expression: Expression::Malformed(
+                            expected_span(indent_prefix.len(), line),
+                            line.to_string()
+                        ),
                         indent_suffix: indent_suffix.to_string(),
                     }
                 );
@@ -279,4 +310,19 @@ mod tests {
         let ssh_config_file2 = read_ssh_config(&tmp_file.path().to_path_buf()).unwrap();
         assert_eq!(ssh_config_file1, ssh_config_file2);
     }
+
+    #[test]
+    fn parse_line_with_embedded_newline_is_malformed_test() {
+        let content = "Host example.com\nUser root";
+        let actual = parse_line(content);
+        assert_eq!(
+            actual,
+            Line {
+                span: expected_span(0, content),
+                indent_prefix: String::new(),
+                expression: Expression::Malformed(expected_span(0, content), content.to_string()),
+                indent_suffix: String::new(),
+            }
+        );
+    }
 }
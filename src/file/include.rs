@@ -0,0 +1,271 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::{read_ssh_config, ArgumentToken, Expression, File, Line};
+
+/// Maximum number of nested `Include` directives we are willing to follow.
+///
+/// OpenSSH itself caps recursion (at the time of writing, 16 levels); we mirror that so a
+/// misconfigured chain of includes fails with a clear error instead of recursing forever.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// A [`Line`] together with the path of the file it was read from.
+///
+/// `Include` resolution flattens several files into one sequence, so each line needs to keep
+/// track of where it actually came from.
+#[derive(PartialEq, Debug)]
+pub struct ResolvedLine {
+    /// Path of the file this line was parsed from, or `None` if the originating [`File`] was
+    /// parsed without a path.
+    pub path: Option<PathBuf>,
+    /// The line itself.
+    pub line: Line,
+}
+
+/// Result of resolving every `Include` directive in a [`File`] into a single flat sequence.
+#[derive(PartialEq, Debug)]
+pub struct ResolvedConfig {
+    /// Lines of every included file, in the order ssh would apply them, each tagged with its
+    /// originating path.
+    pub lines: Vec<ResolvedLine>,
+}
+
+/// Resolves every `Include` directive in `file`, recursively reading and splicing in the files
+/// it references, and returns the flattened result.
+///
+/// `base_dir` is the directory the `Include` arguments of `file` itself are resolved relative to
+/// (for a top-level user config this is typically `~/.ssh`); the `Include` arguments of an
+/// included file are resolved relative to that file's own directory. A leading `~` in an argument
+/// is expanded against the `HOME` environment variable regardless of `base_dir`.
+///
+/// An `Include` argument that matches no files is silently skipped, matching ssh's own behaviour;
+/// this returns an error only if an included file or a directory being globbed can't be read, if
+/// `HOME` is needed to expand a `~` but is not set, or if following `Include` directives would
+/// cycle back to a file already being resolved or exceed [`MAX_INCLUDE_DEPTH`].
+pub fn resolve_includes(file: &File, base_dir: &Path) -> io::Result<ResolvedConfig> {
+    let mut visited = HashSet::<PathBuf>::new();
+    if let Some(path) = &file.path {
+        if let Ok(canonical) = fs::canonicalize(path) {
+            visited.insert(canonical);
+        }
+    }
+
+    let mut lines = Vec::<ResolvedLine>::new();
+    splice_lines(file, base_dir, &mut visited, 0, &mut lines)?;
+    Ok(ResolvedConfig { lines })
+}
+
+fn splice_lines(
+    file: &File,
+    base_dir: &Path,
+    visited: &mut HashSet<PathBuf>,
+    depth: usize,
+    out: &mut Vec<ResolvedLine>,
+) -> io::Result<()> {
+    for line in &file.lines {
+        let Expression::ConfigurationOptions {
+            keyword,
+            arguments_expression,
+            ..
+        } = &line.expression
+        else {
+            out.push(ResolvedLine {
+                path: file.path.clone(),
+                line: line.clone(),
+            });
+            continue;
+        };
+
+        if !keyword.eq_ignore_ascii_case("include") {
+            out.push(ResolvedLine {
+                path: file.path.clone(),
+                line: line.clone(),
+            });
+            continue;
+        }
+
+        if depth >= MAX_INCLUDE_DEPTH {
+            return Err(io::Error::other(format!(
+                "Include nesting exceeds maximum depth of {MAX_INCLUDE_DEPTH}"
+            )));
+        }
+
+        for pattern in argument_values(arguments_expression) {
+            for included_path in expand_pattern(&pattern, base_dir)? {
+                let canonical = fs::canonicalize(&included_path)?;
+                if !visited.insert(canonical.clone()) {
+                    return Err(io::Error::other(format!(
+                        "Include cycle detected at {}",
+                        included_path.display()
+                    )));
+                }
+
+                let included_file = read_ssh_config(&included_path)?;
+                let included_base_dir = included_path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_else(|| base_dir.to_path_buf());
+                splice_lines(&included_file, &included_base_dir, visited, depth + 1, out)?;
+
+                visited.remove(&canonical);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Extracts the non-whitespace argument values of a `Configuration Options` expression, without
+/// attempting to unescape quoted arguments.
+fn argument_values(arguments_expression: &[ArgumentToken]) -> Vec<String> {
+    arguments_expression
+        .iter()
+        .filter_map(|token| match token {
+            ArgumentToken::Pure(value) | ArgumentToken::Quoted(value) => Some(value.clone()),
+            ArgumentToken::Whitespace(_) => None,
+        })
+        .collect()
+}
+
+/// Expands a single `Include` argument into the list of files it matches, resolving `~` against
+/// `HOME` and otherwise treating relative patterns as relative to `base_dir`.
+fn expand_pattern(pattern: &str, base_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let expanded = if let Some(rest) = pattern.strip_prefix('~') {
+        let home = std::env::var("HOME")
+            .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?;
+        PathBuf::from(home).join(rest.trim_start_matches('/'))
+    } else {
+        let candidate = PathBuf::from(pattern);
+        if candidate.is_absolute() {
+            candidate
+        } else {
+            base_dir.join(candidate)
+        }
+    };
+
+    let mut matches = glob_paths(&expanded)?;
+    matches.sort();
+    Ok(matches)
+}
+
+/// Walks `pattern` component by component, expanding any `*`/`?` glob characters against the
+/// filesystem, and returns every existing path that matches.
+fn glob_paths(pattern: &Path) -> io::Result<Vec<PathBuf>> {
+    let is_absolute = pattern.is_absolute();
+    let components: Vec<String> = pattern
+        .components()
+        .filter(|c| !matches!(c, std::path::Component::RootDir))
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    let root = if is_absolute {
+        PathBuf::from("/")
+    } else {
+        PathBuf::new()
+    };
+    let mut current = vec![root];
+
+    for component in &components {
+        let mut next = Vec::new();
+        for base in &current {
+            if component.contains('*') || component.contains('?') {
+                if !base.is_dir() {
+                    continue;
+                }
+                for entry in fs::read_dir(base)? {
+                    let entry = entry?;
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    if glob_component_matches(component, &name) {
+                        next.push(entry.path());
+                    }
+                }
+            } else {
+                let candidate = base.join(component);
+                if candidate.exists() {
+                    next.push(candidate);
+                }
+            }
+        }
+        current = next;
+    }
+
+    Ok(current)
+}
+
+/// Matches a single path component against a glob pattern supporting `*` (any run of
+/// characters) and `?` (exactly one character).
+fn glob_component_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.first() {
+            None => name.is_empty(),
+            Some('*') => {
+                matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..]))
+            }
+            Some('?') => !name.is_empty() && matches(&pattern[1..], &name[1..]),
+            Some(c) => name.first() == Some(c) && matches(&pattern[1..], &name[1..]),
+        }
+    }
+
+    matches(&pattern, &name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use super::super::parse_ssh_config;
+
+    #[test]
+    fn resolves_single_include_test() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("conf.d")).unwrap();
+        let mut included = std::fs::File::create(dir.path().join("conf.d/a.conf")).unwrap();
+        writeln!(included, "User root").unwrap();
+
+        let main = parse_ssh_config(
+            "Include conf.d/*.conf\nHost example.com\n",
+            Some(dir.path().join("config")),
+        );
+
+        let resolved = resolve_includes(&main, dir.path()).unwrap();
+        assert_eq!(resolved.lines.len(), 2);
+        assert_eq!(
+            resolved.lines[0].path,
+            Some(dir.path().join("conf.d/a.conf"))
+        );
+        assert!(matches!(
+            resolved.lines[1].line.expression,
+            Expression::ConfigurationOptions { ref keyword, .. } if keyword == "Host"
+        ));
+    }
+
+    #[test]
+    fn detects_include_cycle_test() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config");
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, "Include config").unwrap();
+
+        let main = read_ssh_config(&path).unwrap();
+        let err = resolve_includes(&main, dir.path()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn glob_component_matches_test() {
+        assert!(glob_component_matches("*.conf", "a.conf"));
+        assert!(glob_component_matches("a?c", "abc"));
+        assert!(!glob_component_matches("a?c", "ac"));
+        assert!(!glob_component_matches("*.conf", "a.txt"));
+    }
+}
+
@@ -1,4 +1,4 @@
-use std::{fmt, path::PathBuf};
+use std::{borrow::Cow, fmt, path::PathBuf};
 
 /// [ssh_config(5)](https://linux.die.net/man/5/ssh_config) file is a sequence of entries, an entry is either a *keyword argument* line, or a *comment* line.
 ///
@@ -44,9 +44,30 @@ impl fmt::Display for File {
     }
 }
 
+/// A precise location in ssh_config source text.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Position {
+    /// Byte offset from the start of the file.
+    pub offset: usize,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column number, counted in characters.
+    pub column: usize,
+}
+
+/// A half-open range of source text, from `start` (inclusive) to `end` (exclusive).
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
 /// Line is a sequence of characters in ssh_config file followed by /n or /r/n.
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct Line {
+    /// Span of the entire physical line, including [`indent_prefix`] and [`indent_suffix`], in
+    /// the source it was parsed from.
+    pub span: Span,
     /// Indent prefix is the longest possible line prefix consisting of whitespace symbols.
     ///
     /// It is convenient to keep it to be able to restore original line formatting.
@@ -72,10 +93,12 @@ impl fmt::Display for Line {
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum Expression {
     /// Configuration options is an entry of a keyword-argument format.
     ConfigurationOptions {
+        /// Span of `keyword`, `separator` and `arguments_expression` combined.
+        span: Span,
         /// Keyword is a case-insensitive token consisting of `[A-Za-z]` and corresponding to the field being set.
         keyword: String,
         /// Separator is a string splitting keyword and arguments.
@@ -93,11 +116,11 @@ pub enum Expression {
     ///
     /// Please, note that genuine understanding of comment lines in [ssh_config(5)](https://linux.die.net/man/5/ssh_config)
     /// is different from what we call a comment, because we distinguish [`Empty`] expression as a separate case, not as a comment.
-    Comment(String),
-    /// Empty expression is an empty string.
-    Empty,
+    Comment(Span, String),
+    /// Empty expression is an empty string, at the given (zero-width) span.
+    Empty(Span),
     /// Any string not being a valid [`ConfigurationOptions`], [`Comment`] or [`Empty`] expression.
-    Malformed(String),
+    Malformed(Span, String),
 }
 
 impl fmt::Display for Expression {
@@ -107,6 +130,7 @@ impl fmt::Display for Expression {
                 keyword,
                 separator,
                 arguments_expression,
+                ..
             } => write!(
                 f,
                 "{}{}{}",
@@ -118,9 +142,9 @@ impl fmt::Display for Expression {
                     .collect::<Vec::<String>>()
                     .join("")
             ),
-            Expression::Comment(comment) => write!(f, "{}", comment),
-            Expression::Empty => write!(f, ""),
-            Expression::Malformed(malformed) => write!(f, "{}", malformed),
+            Expression::Comment(_, comment) => write!(f, "{}", comment),
+            Expression::Empty(_) => write!(f, ""),
+            Expression::Malformed(_, malformed) => write!(f, "{}", malformed),
         }
     }
 }
@@ -156,5 +180,120 @@ impl fmt::Display for ArgumentToken {
     }
 }
 
+impl ArgumentToken {
+    /// Returns the logical value of this token, resolving quoting.
+    ///
+    /// For [`Quoted`](ArgumentToken::Quoted), `\"` is unescaped to `"` and `\\` to `\`, leaving
+    /// any other backslash intact, matching how ssh itself treats quoted arguments. For
+    /// [`Pure`](ArgumentToken::Pure), the value is returned as-is, since it may not contain
+    /// quoting characters in the first place. [`Whitespace`](ArgumentToken::Whitespace) has no
+    /// logical value; an empty string is returned.
+    pub fn resolved_value(&self) -> Cow<'_, str> {
+        match self {
+            ArgumentToken::Pure(value) => Cow::Borrowed(value),
+            ArgumentToken::Quoted(value) => {
+                if !value.contains('\\') {
+                    return Cow::Borrowed(value);
+                }
+
+                let mut resolved = String::with_capacity(value.len());
+                let mut chars = value.chars();
+                while let Some(c) = chars.next() {
+                    if c == '\\' {
+                        match chars.clone().next() {
+                            Some('"') => {
+                                resolved.push('"');
+                                chars.next();
+                            }
+                            Some('\\') => {
+                                resolved.push('\\');
+                                chars.next();
+                            }
+                            _ => resolved.push('\\'),
+                        }
+                    } else {
+                        resolved.push(c);
+                    }
+                }
+                Cow::Owned(resolved)
+            }
+            ArgumentToken::Whitespace(_) => Cow::Borrowed(""),
+        }
+    }
+
+    /// Builds an [`ArgumentToken`] carrying the logical value `s`, choosing the most natural
+    /// representation: [`Pure`](ArgumentToken::Pure) when `s` contains none of the characters
+    /// that would need quoting or escaping, and [`Quoted`](ArgumentToken::Quoted) (escaping `"`
+    /// and `\`) otherwise.
+    ///
+    /// Round-tripping is stable: `ArgumentToken::from_value(token.resolved_value().as_ref())`
+    /// yields a token whose [`resolved_value`](ArgumentToken::resolved_value) is `s` again.
+    pub fn from_value(s: &str) -> ArgumentToken {
+        if s.chars().any(|c| c.is_whitespace() || c == '"' || c == '#') {
+            let mut quoted = String::with_capacity(s.len());
+            for c in s.chars() {
+                if c == '"' || c == '\\' {
+                    quoted.push('\\');
+                }
+                quoted.push(c);
+            }
+            ArgumentToken::Quoted(quoted)
+        } else {
+            ArgumentToken::Pure(s.to_string())
+        }
+    }
+}
+
 /// Whitespace string is a string consisting only of space (`' '`) or tabular (`'\t'`) symbols
 type WhitespaceString = String;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolved_value_test() {
+        assert_eq!(
+            ArgumentToken::Pure("example.com".to_string()).resolved_value(),
+            "example.com"
+        );
+        assert_eq!(
+            ArgumentToken::Quoted("hello # \\\" lol ".to_string()).resolved_value(),
+            "hello # \" lol "
+        );
+        assert_eq!(
+            ArgumentToken::Quoted("a\\\\b".to_string()).resolved_value(),
+            "a\\b"
+        );
+        assert_eq!(
+            ArgumentToken::Quoted("trailing\\".to_string()).resolved_value(),
+            "trailing\\"
+        );
+    }
+
+    #[test]
+    fn from_value_round_trip_test() {
+        for value in [
+            "example.com",
+            "with space",
+            "quote\"inside",
+            "hash#inside",
+            "back\\slash",
+        ] {
+            let token = ArgumentToken::from_value(value);
+            assert_eq!(token.resolved_value(), value);
+        }
+    }
+
+    #[test]
+    fn from_value_picks_pure_when_possible_test() {
+        assert_eq!(
+            ArgumentToken::from_value("example.com"),
+            ArgumentToken::Pure("example.com".to_string())
+        );
+        assert_eq!(
+            ArgumentToken::from_value("with space"),
+            ArgumentToken::Quoted("with space".to_string())
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use super::{ArgumentToken, Expression, File};
+
+impl File {
+    /// Computes the effective configuration for `hostname`, following the same `Host`/`Match`
+    /// block semantics and "first value wins" rule as [ssh_config(5)](https://linux.die.net/man/5/ssh_config).
+    ///
+    /// Lines are walked top to bottom, tracking whether the currently active `Host`/`Match`
+    /// block applies to `hostname`; lines outside of any block (i.e. before the first `Host`/
+    /// `Match` line) are always active. For each keyword encountered while its enclosing block
+    /// is active, only the first set of argument values seen is kept — later occurrences of the
+    /// same keyword, even in a different matching block, are ignored.
+    ///
+    /// `Match` is only given rudimentary support: `all` always matches, `host` compares against
+    /// `hostname` exactly like a `Host` pattern-list, and `canonical` is treated as always
+    /// matching, since this crate performs no hostname canonicalization of its own.
+    pub fn effective_config(&self, hostname: &str) -> BTreeMap<String, Vec<String>> {
+        let mut config = BTreeMap::<String, Vec<String>>::new();
+        let mut active = true;
+
+        for line in &self.lines {
+            let Expression::ConfigurationOptions {
+                keyword,
+                arguments_expression,
+                ..
+            } = &line.expression
+            else {
+                continue;
+            };
+
+            let values = argument_values(arguments_expression);
+
+            if keyword.eq_ignore_ascii_case("host") {
+                active = host_patterns_match(&values, hostname);
+                continue;
+            }
+
+            if keyword.eq_ignore_ascii_case("match") {
+                active = match_criteria_match(&values, hostname);
+                continue;
+            }
+
+            if active {
+                config
+                    .entry(keyword.to_lowercase())
+                    .or_insert_with(|| values.clone());
+            }
+        }
+
+        config
+    }
+}
+
+/// Extracts the non-whitespace argument values of a `ConfigurationOptions` expression, resolving
+/// quoting via [`ArgumentToken::resolved_value`].
+fn argument_values(arguments_expression: &[ArgumentToken]) -> Vec<String> {
+    arguments_expression
+        .iter()
+        .filter_map(|token| match token {
+            ArgumentToken::Pure(_) | ArgumentToken::Quoted(_) => {
+                Some(token.resolved_value().into_owned())
+            }
+            ArgumentToken::Whitespace(_) => None,
+        })
+        .collect()
+}
+
+/// Evaluates a `Host` pattern-list (as found in the arguments of a `Host` line) against
+/// `hostname`, honouring `!`-negated patterns: `hostname` matches if it matches at least one
+/// non-negated pattern and no negated pattern.
+fn host_patterns_match(patterns: &[String], hostname: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if host_pattern_matches(negated, hostname) {
+                return false;
+            }
+        } else if host_pattern_matches(pattern, hostname) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+/// Evaluates a `Match` criteria list (as found in the arguments of a `Match` line).
+fn match_criteria_match(criteria: &[String], hostname: &str) -> bool {
+    let mut iter = criteria.iter();
+    while let Some(criterion) = iter.next() {
+        let matches = if criterion.eq_ignore_ascii_case("all")
+            || criterion.eq_ignore_ascii_case("canonical")
+        {
+            // `canonical` is treated as always matching, since this crate performs no hostname
+            // canonicalization of its own.
+            true
+        } else if criterion.eq_ignore_ascii_case("host") {
+            let patterns: Vec<String> = iter
+                .next()
+                .map(|arg| arg.split(',').map(str::to_string).collect())
+                .unwrap_or_default();
+            host_patterns_match(&patterns, hostname)
+        } else {
+            // Unsupported criterion (e.g. `user`, `exec`): skip its value, if any, and don't
+            // let it block the match on its own.
+            iter.next();
+            true
+        };
+
+        if !matches {
+            return false;
+        }
+    }
+    true
+}
+
+/// Matches `hostname` against a single `Host`/`Match host` glob pattern supporting `*` (any run
+/// of characters) and `?` (exactly one character).
+fn host_pattern_matches(pattern: &str, hostname: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let hostname: Vec<char> = hostname.chars().collect();
+
+    fn matches(pattern: &[char], hostname: &[char]) -> bool {
+        match pattern.first() {
+            None => hostname.is_empty(),
+            Some('*') => {
+                matches(&pattern[1..], hostname)
+                    || (!hostname.is_empty() && matches(pattern, &hostname[1..]))
+            }
+            Some('?') => !hostname.is_empty() && matches(&pattern[1..], &hostname[1..]),
+            Some(c) => hostname.first() == Some(c) && matches(&pattern[1..], &hostname[1..]),
+        }
+    }
+
+    matches(&pattern, &hostname)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_ssh_config;
+
+    #[test]
+    fn effective_config_picks_first_matching_block_test() {
+        let file = parse_ssh_config(
+            "Host *.example.com !internal.example.com\n\
+             \tUser alice\n\
+             \tPort 2222\n\
+             Host internal.example.com\n\
+             \tUser bob\n\
+             Host *\n\
+             \tUser nobody\n\
+             \tCompression yes\n",
+            None,
+        );
+
+        let config = file.effective_config("foo.example.com");
+        assert_eq!(config.get("user"), Some(&vec!["alice".to_string()]));
+        assert_eq!(config.get("port"), Some(&vec!["2222".to_string()]));
+        assert_eq!(config.get("compression"), Some(&vec!["yes".to_string()]));
+
+        let config = file.effective_config("internal.example.com");
+        assert_eq!(config.get("user"), Some(&vec!["bob".to_string()]));
+        assert!(!config.contains_key("port"));
+    }
+
+    #[test]
+    fn effective_config_match_host_test() {
+        let file = parse_ssh_config(
+            "Match host foo.example.com\n\
+             \tUser alice\n\
+             Match all\n\
+             \tUser nobody\n",
+            None,
+        );
+
+        assert_eq!(
+            file.effective_config("foo.example.com").get("user"),
+            Some(&vec!["alice".to_string()])
+        );
+        assert_eq!(
+            file.effective_config("bar.example.com").get("user"),
+            Some(&vec!["nobody".to_string()])
+        );
+    }
+
+    #[test]
+    fn host_pattern_glob_test() {
+        use super::host_pattern_matches;
+        assert!(host_pattern_matches("*.example.com", "foo.example.com"));
+        assert!(host_pattern_matches("fo?.example.com", "foo.example.com"));
+        assert!(!host_pattern_matches("*.example.com", "example.com"));
+        assert!(host_pattern_matches("*", "anything"));
+    }
+}